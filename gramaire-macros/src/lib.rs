@@ -0,0 +1,145 @@
+//! Compile-time companion macro for `gramaire`.
+//!
+//! `spell!("md__hover:color=red")` parses its string literal at compile time
+//! and fails the build on invalid input — the DSL equivalent of compile-time
+//! Tailwind class checking. A literal naming a real CSS property expands to its
+//! rendered CSS string (so it costs nothing to parse at runtime); a literal
+//! naming a registry component — which cannot be resolved at compile time —
+//! expands to the constructed [`gramaire::Spell`] value instead.
+//!
+//! Hard errors (parse failures, unknown breakpoints) abort the build via
+//! `compile_error!`. Soft concerns that might still be valid at runtime — a
+//! `component` that is neither a known property nor provably a component, or an
+//! `effect` naming a pseudo-class outside the allow-list — surface as
+//! deprecation warnings on stable, since proc-macros cannot emit warnings
+//! directly there.
+
+use proc_macro::{TokenStream, TokenTree};
+
+use gramaire::Spell;
+
+/// CSS properties recognised as valid `component` names. A `component` outside
+/// this set is assumed to name a [`gramaire::Registry`] component, which can
+/// only be resolved at runtime.
+const KNOWN_PROPERTIES: &[&str] = &[
+    "background",
+    "background-color",
+    "border",
+    "border-color",
+    "border-radius",
+    "color",
+    "display",
+    "flex",
+    "font-size",
+    "font-weight",
+    "gap",
+    "height",
+    "margin",
+    "opacity",
+    "padding",
+    "position",
+    "width",
+    "z-index",
+];
+
+/// Pseudo-classes accepted in an `effect` without a warning.
+const KNOWN_PSEUDO_CLASSES: &[&str] = &[
+    "active",
+    "checked",
+    "disabled",
+    "first-child",
+    "focus",
+    "focus-visible",
+    "focus-within",
+    "hover",
+    "last-child",
+    "link",
+    "visited",
+];
+
+/// Parse and validate a spell literal at compile time.
+///
+/// For a real CSS property the macro expands to the rendered CSS string; for a
+/// registry component it expands to the constructed `Spell` value, since the
+/// registry is only known at runtime.
+#[proc_macro]
+pub fn spell(input: TokenStream) -> TokenStream {
+    let literal = match string_literal(input) {
+        Ok(literal) => literal,
+        Err(message) => return compile_error(&message),
+    };
+
+    let spell = match literal.parse::<Spell>() {
+        Ok(spell) => spell,
+        Err(err) => return compile_error(&err.render(&literal)),
+    };
+
+    let is_property = KNOWN_PROPERTIES.contains(&spell.component());
+
+    let mut warnings = Vec::new();
+    if !is_property {
+        warnings.push(format!(
+            "`{}` is not a known CSS property; assuming a registered component",
+            spell.component()
+        ));
+    }
+    if let Some(effect) = spell.effect() {
+        for pseudo in effect {
+            if !KNOWN_PSEUDO_CLASSES.contains(&pseudo.as_str()) {
+                warnings.push(format!("`{}` is not a known pseudo-class", pseudo));
+            }
+        }
+    }
+
+    let expansion = if is_property {
+        // Real CSS property: render to a CSS string literal at compile time.
+        format!("{:?}", spell.to_css())
+    } else {
+        // Registry component (or unknown property): the registry is not
+        // available at compile time, so expand to the constructed `Spell`
+        // value and leave expansion to `Spell::expand` at runtime.
+        format!("{:?}.parse::<::gramaire::Spell>().unwrap()", literal)
+    };
+
+    with_warnings(&expansion, &warnings)
+}
+
+/// Emit `expansion`, prefixed on stable by a `#[deprecated]` shim per warning
+/// so each concern shows up as a compiler warning at the call site.
+fn with_warnings(expansion: &str, warnings: &[String]) -> TokenStream {
+    if warnings.is_empty() {
+        return expansion.parse().unwrap();
+    }
+    let mut block = String::from("{");
+    for (i, message) in warnings.iter().enumerate() {
+        block.push_str(&format!(
+            "#[deprecated(note = {message:?})] fn spell_warning_{i}() {{}} spell_warning_{i}();"
+        ));
+    }
+    block.push_str(expansion);
+    block.push('}');
+    block.parse().unwrap()
+}
+
+/// Extract the single string literal the macro was invoked with.
+fn string_literal(input: TokenStream) -> Result<String, String> {
+    let mut trees = input.into_iter();
+    let literal = match trees.next() {
+        Some(TokenTree::Literal(literal)) => literal.to_string(),
+        _ => return Err("spell! expects a single string literal".to_string()),
+    };
+    if trees.next().is_some() {
+        return Err("spell! expects a single string literal".to_string());
+    }
+    // `Literal::to_string` keeps the surrounding quotes; strip them.
+    let unquoted = literal
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or_else(|| "spell! expects a string literal".to_string())?;
+    Ok(unquoted.to_string())
+}
+
+/// Build a `compile_error!` invocation carrying `message`.
+fn compile_error(message: &str) -> TokenStream {
+    format!("compile_error!({:?})", message).parse().unwrap()
+}
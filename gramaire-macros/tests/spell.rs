@@ -0,0 +1,25 @@
+use gramaire::Spell;
+use gramaire_macros::spell;
+
+#[test]
+fn property_expands_to_css_string() {
+    // A real CSS property renders to a CSS string at compile time.
+    let css: &str = spell!("md__hover:color=red");
+    assert_eq!(css, "@media (min-width: 768px) { :hover { color: red; } }");
+}
+
+#[test]
+#[allow(deprecated)] // `btn` is not a known property, so the macro warns by design.
+fn component_expands_to_spell_value() {
+    // A registry component cannot be resolved at compile time, so the macro
+    // expands to the constructed `Spell` value instead of bogus CSS.
+    let spell: Spell = spell!("btn=8px_lightgrey_grey_darkgrey");
+    assert_eq!(spell, "btn=8px_lightgrey_grey_darkgrey".parse().unwrap());
+    assert_eq!(spell.component(), "btn");
+}
+
+#[test]
+fn compile_fail_cases() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,7 @@
+use gramaire_macros::spell;
+
+fn main() {
+    // Missing `=` after the component: must fail the build with a caret
+    // diagnostic rather than compiling.
+    let _ = spell!("color");
+}
@@ -1,6 +1,57 @@
+use std::collections::HashMap;
+use std::ops::Range;
 use std::str::FromStr;
 
+/// A parse failure carrying the byte offsets of the offending span in the
+/// original input, so callers can point a caret at it.
 #[derive(Debug, PartialEq)]
+pub struct SpellError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl SpellError {
+    fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        SpellError { message: message.into(), span }
+    }
+
+    /// Shift the span by `by` bytes, used when a sub-parser reports an offset
+    /// relative to a slice of the original input.
+    fn offset(mut self, by: usize) -> Self {
+        self.span.start += by;
+        self.span.end += by;
+        self
+    }
+
+    /// Produce an ariadne-style report: the offending source line, a caret
+    /// underline beneath the span, and the message.
+    pub fn render(&self, src: &str) -> String {
+        let start = self.span.start.min(src.len());
+        let line_start = src[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = src[start..]
+            .find('\n')
+            .map(|i| start + i)
+            .unwrap_or(src.len());
+        let line = &src[line_start..line_end];
+        let line_no = src[..line_start].matches('\n').count() + 1;
+        let col = start - line_start;
+        let width = (self.span.end - self.span.start).max(1);
+
+        let gutter = format!("{} | ", line_no);
+        let mut out = String::new();
+        out.push_str(&gutter);
+        out.push_str(line);
+        out.push('\n');
+        out.push_str(&" ".repeat(gutter.len() + col));
+        out.push_str(&"^".repeat(width));
+        out.push(' ');
+        out.push_str(&self.message);
+        out.push('\n');
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Spell {
     area: Option<SpellArea>,
     focus: Option<String>,
@@ -9,28 +60,31 @@ pub struct Spell {
     target: SpellTarget,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum SpellArea {
     Breakpoint(SpellBreakpoint),
     MediaQuery(String),
 }
 
 impl FromStr for SpellArea {
-    type Err = &'static str;
+    type Err = SpellError;
 
     fn from_str(area: &str) -> Result<Self, Self::Err> {
         match area.chars().next() {
             Some('(') => match area.find(')') {
                 Some(i) => Ok(SpellArea::MediaQuery(String::from(&area[1..i]))),
-                None => Err("missing ')' in spell area"),
+                None => Err(SpellError::new(
+                    "missing ')' in spell area",
+                    area.len()..area.len(),
+                )),
             },
-            None => Err("spell not long enough"),
+            None => Err(SpellError::new("spell not long enough", 0..0)),
             _ => Ok(SpellArea::Breakpoint(area.parse()?)),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum SpellBreakpoint {
     Small,
     Medium,
@@ -40,7 +94,7 @@ enum SpellBreakpoint {
 }
 
 impl FromStr for SpellBreakpoint {
-    type Err = &'static str;
+    type Err = SpellError;
 
     fn from_str(bp: &str) -> Result<Self, Self::Err> {
         Ok(match bp {
@@ -49,19 +103,19 @@ impl FromStr for SpellBreakpoint {
             "lg" => SpellBreakpoint::Large,
             "xl" => SpellBreakpoint::XLarge,
             "xxl" => SpellBreakpoint::XXLarge,
-            _ => return Err("invalid breakpoint for area"),
+            _ => return Err(SpellError::new("invalid breakpoint for area", 0..bp.len())),
         })
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum SpellTarget {
     CSSValue(String),
     Variables(Vec<String>),
 }
 
 impl FromStr for SpellTarget {
-    type Err = &'static str;
+    type Err = SpellError;
 
     fn from_str(target: &str) -> Result<Self, Self::Err> {
         // TODO: will probably need a better check in real-world examples
@@ -70,13 +124,13 @@ impl FromStr for SpellTarget {
             return Ok(Self::CSSValue(String::from(target)));
         }
         let variables: Vec<String> = target.split('_').map(String::from).collect();
-        if variables.is_empty() { return Err("empty target!"); }
+        if variables.is_empty() { return Err(SpellError::new("empty target!", 0..target.len())); }
         Ok(Self::Variables(variables))
     }
 }
 
 impl FromStr for Spell {
-    type Err = &'static str;
+    type Err = SpellError;
 
     fn from_str(spell: &str) -> Result<Self, Self::Err> {
         let area_end = spell.find("__");
@@ -92,16 +146,23 @@ impl FromStr for Spell {
         let focus_len = match spell[focus_start..].chars().next() {
             Some('{') => match spell[focus_start..].find('}') {
                 Some(i) => Some(i),
-                None => return Err("spell ends without closing focus"),
+                None => {
+                    return Err(SpellError::new(
+                        "spell ends without closing focus",
+                        focus_start..spell.len(),
+                    ))
+                }
             },
-            None => return Err("spell ends too early while looking for focus"),
+            None => {
+                return Err(SpellError::new(
+                    "spell ends too early while looking for focus",
+                    focus_start..spell.len(),
+                ))
+            }
             _ => None,
         };
 
-        let focus = match focus_len {
-            Some(i) => Some(String::from(&spell[focus_start+1..focus_start+i])),
-            None => None,
-        };
+        let focus = focus_len.map(|i| String::from(&spell[focus_start+1..focus_start+i]));
 
         let focus_len = focus_len.unwrap_or(0);
 
@@ -131,11 +192,19 @@ impl FromStr for Spell {
 
         let component_len = match spell[component_start..].find('=') {
             Some(i) => i,
-            None => return Err("expected '=' after component but could not find one"),
+            None => {
+                return Err(SpellError::new(
+                    "expected '=' after component but could not find one",
+                    component_start..spell.len(),
+                ))
+            }
         };
         let component = String::from(&spell[component_start..component_start + component_len]);
 
-        let target = spell[component_start + component_len + 1..].parse::<SpellTarget>()?;
+        let target_start = component_start + component_len + 1;
+        let target = spell[target_start..]
+            .parse::<SpellTarget>()
+            .map_err(|e| e.offset(target_start))?;
 
         Ok(Self{
             area,
@@ -147,9 +216,443 @@ impl FromStr for Spell {
     }
 }
 
+impl SpellBreakpoint {
+    /// The DSL token each breakpoint is written as.
+    fn as_str(&self) -> &'static str {
+        match self {
+            SpellBreakpoint::Small => "sm",
+            SpellBreakpoint::Medium => "md",
+            SpellBreakpoint::Large => "lg",
+            SpellBreakpoint::XLarge => "xl",
+            SpellBreakpoint::XXLarge => "xxl",
+        }
+    }
+
+    /// The `min-width` each breakpoint maps to in a generated `@media` query.
+    fn min_width(&self) -> &'static str {
+        match self {
+            SpellBreakpoint::Small => "640px",
+            SpellBreakpoint::Medium => "768px",
+            SpellBreakpoint::Large => "1024px",
+            SpellBreakpoint::XLarge => "1280px",
+            SpellBreakpoint::XXLarge => "1536px",
+        }
+    }
+}
+
+impl Spell {
+    /// The selector the rule applies to, rebuilt from `focus` and `effect`.
+    ///
+    /// `_` separators in `focus` become the whitespace/combinators they stood
+    /// in for, and each `effect` entry is appended as a `:pseudo-class`.
+    fn selector(&self) -> String {
+        let mut selector = String::new();
+        if let Some(focus) = &self.focus {
+            selector.push_str(&focus.replace('_', " "));
+        }
+        if let Some(effect) = &self.effect {
+            for pseudo in effect {
+                selector.push(':');
+                selector.push_str(pseudo);
+            }
+        }
+        selector
+    }
+
+    /// The declaration body (`component` + `target`) without the surrounding block.
+    fn declarations(&self) -> String {
+        let value = match &self.target {
+            SpellTarget::CSSValue(value) => value.clone(),
+            SpellTarget::Variables(variables) => variables.join(" "),
+        };
+        format!("{}: {};", self.component, value)
+    }
+
+    /// Render the parsed spell into a CSS rule, wrapping it in an `@media`
+    /// block when the spell carries an area.
+    pub fn to_css(&self) -> String {
+        wrap_area(&self.area, block(&self.selector(), &self.declarations()))
+    }
+
+    /// Render the spell, minifying the output when `options.minify` is set.
+    pub fn to_css_with(&self, options: CssOptions) -> String {
+        let css = self.to_css();
+        if options.minify {
+            minify(&css)
+        } else {
+            css
+        }
+    }
+
+    /// Render the spell with whitespace collapsed and redundant tokens dropped.
+    pub fn to_css_minified(&self) -> String {
+        self.to_css_with(CssOptions { minify: true })
+    }
+
+    /// The `component` (CSS property or shorthand name) of the spell.
+    pub fn component(&self) -> &str {
+        &self.component
+    }
+
+    /// The pseudo-class `effect` entries, if any.
+    pub fn effect(&self) -> Option<&[String]> {
+        self.effect.as_deref()
+    }
+
+    /// Serialize the spell back into its DSL form.
+    ///
+    /// Unlike [`Spell::to_css`] (and the `Display` impl, which renders CSS),
+    /// this reproduces the source spell syntax, so `spell.to_spell().parse()`
+    /// round-trips back to an equal `Spell`.
+    pub fn to_spell(&self) -> String {
+        let mut out = String::new();
+        if let Some(area) = &self.area {
+            match area {
+                SpellArea::Breakpoint(bp) => out.push_str(bp.as_str()),
+                SpellArea::MediaQuery(query) => {
+                    out.push('(');
+                    out.push_str(query);
+                    out.push(')');
+                }
+            }
+            out.push_str("__");
+        }
+        if let Some(focus) = &self.focus {
+            out.push('{');
+            out.push_str(focus);
+            out.push('}');
+        }
+        if let Some(effect) = &self.effect {
+            out.push_str(&effect.join(","));
+            out.push(':');
+        }
+        out.push_str(&self.component);
+        out.push('=');
+        match &self.target {
+            SpellTarget::CSSValue(value) => out.push_str(value),
+            SpellTarget::Variables(variables) => out.push_str(&variables.join("_")),
+        }
+        out
+    }
+}
+
+/// Wrap a rule in an `@media` block when the spell carries an area.
+fn wrap_area(area: &Option<SpellArea>, rule: String) -> String {
+    match area {
+        Some(SpellArea::Breakpoint(bp)) => {
+            format!("@media (min-width: {}) {{ {} }}", bp.min_width(), rule)
+        }
+        Some(SpellArea::MediaQuery(query)) => format!("@media ({}) {{ {} }}", query, rule),
+        None => rule,
+    }
+}
+
+/// Assemble a selector and declaration body into a rule block.
+fn block(selector: &str, body: &str) -> String {
+    if selector.is_empty() {
+        format!("{{ {} }}", body)
+    } else {
+        format!("{} {{ {} }}", selector, body)
+    }
+}
+
+/// Options controlling CSS emission.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CssOptions {
+    /// Collapse whitespace and drop redundant tokens from the output.
+    pub minify: bool,
+}
+
+/// Render many spells at once, merging those that share a
+/// `(area, focus, effect)` selector into a single rule block rather than
+/// emitting the selector more than once.
+pub fn render_all(spells: &[Spell], options: CssOptions) -> String {
+    // Group by selector key, preserving first-seen order so the output is
+    // deterministic without requiring `Hash`/`Eq` on the area enums.
+    let mut groups: Vec<(&Spell, Vec<String>)> = Vec::new();
+    for spell in spells {
+        let slot = groups.iter_mut().find(|(rep, _)| {
+            rep.area == spell.area && rep.focus == spell.focus && rep.effect == spell.effect
+        });
+        match slot {
+            Some((_, decls)) => decls.push(spell.declarations()),
+            None => groups.push((spell, vec![spell.declarations()])),
+        }
+    }
+
+    let css = groups
+        .iter()
+        .map(|(rep, decls)| wrap_area(&rep.area, block(&rep.selector(), &decls.join(" "))))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if options.minify {
+        minify(&css)
+    } else {
+        css
+    }
+}
+
+/// A token in the generated CSS, as produced by [`tokenize`].
+enum CssToken {
+    Whitespace,
+    Ident(String),
+    Colon,
+    Semicolon,
+    Str(String),
+    Char(char),
+}
+
+/// Split generated CSS into a token stream off a `Peekable<CharIndices>`.
+fn tokenize(css: &str) -> Vec<CssToken> {
+    fn is_ident(c: char) -> bool {
+        c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '#' | '%' | '@')
+    }
+
+    let mut chars = css.char_indices().peekable();
+    let mut tokens = Vec::new();
+    while let Some(&(_, c)) = chars.peek() {
+        if c.is_whitespace() {
+            while matches!(chars.peek(), Some(&(_, c)) if c.is_whitespace()) {
+                chars.next();
+            }
+            tokens.push(CssToken::Whitespace);
+        } else if c == ':' {
+            chars.next();
+            tokens.push(CssToken::Colon);
+        } else if c == ';' {
+            chars.next();
+            tokens.push(CssToken::Semicolon);
+        } else if c == '"' || c == '\'' {
+            let quote = c;
+            chars.next();
+            let mut s = String::new();
+            s.push(quote);
+            for (_, c) in chars.by_ref() {
+                s.push(c);
+                if c == quote {
+                    break;
+                }
+            }
+            tokens.push(CssToken::Str(s));
+        } else if is_ident(c) {
+            let mut s = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if is_ident(c) {
+                    s.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(CssToken::Ident(s));
+        } else {
+            chars.next();
+            tokens.push(CssToken::Char(c));
+        }
+    }
+    tokens
+}
+
+/// Tokenize and re-emit `css` compactly: strip whitespace around punctuation,
+/// drop the `;` before a closing brace, and keep a single space only between
+/// two word-like tokens (descendant combinators, multi-word values).
+fn minify(css: &str) -> String {
+    fn word_like(token: Option<&CssToken>) -> bool {
+        matches!(token, Some(CssToken::Ident(_)) | Some(CssToken::Str(_)))
+    }
+
+    let tokens = tokenize(css);
+    let mut out = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            CssToken::Whitespace => {
+                let prev = tokens[..i]
+                    .iter()
+                    .rev()
+                    .find(|t| !matches!(t, CssToken::Whitespace));
+                let next = tokens[i + 1..]
+                    .iter()
+                    .find(|t| !matches!(t, CssToken::Whitespace));
+                if word_like(prev) && word_like(next) {
+                    out.push(' ');
+                }
+            }
+            CssToken::Ident(s) | CssToken::Str(s) => out.push_str(s),
+            CssToken::Colon => out.push(':'),
+            CssToken::Semicolon => {
+                let next = tokens[i + 1..]
+                    .iter()
+                    .find(|t| !matches!(t, CssToken::Whitespace));
+                if !matches!(next, Some(CssToken::Char('}'))) {
+                    out.push(';');
+                }
+            }
+            CssToken::Char(c) => out.push(*c),
+        }
+    }
+    out
+}
+
+impl std::fmt::Display for Spell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_css())
+    }
+}
+
+/// A store of component templates that expand shorthand spells into real CSS
+/// declarations. A template is a list of `(property, value)` pairs whose values
+/// may reference the spell's positional `Variables` as `$0`, `$1`, ….
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    components: HashMap<String, Vec<(String, String)>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Register a component from a declaration template such as
+    /// `"border-radius: $0; background: $1; border-color: $2; color: $3;"`.
+    pub fn register(&mut self, name: impl Into<String>, template: &str) {
+        let declarations = template
+            .split(';')
+            .filter_map(|decl| {
+                let (property, value) = decl.split_once(':')?;
+                Some((property.trim().to_string(), value.trim().to_string()))
+            })
+            .collect();
+        self.components.insert(name.into(), declarations);
+    }
+}
+
+impl Spell {
+    /// Expand a shorthand spell against `reg`, binding its positional
+    /// `Variables` into the registered template. A spell whose `component` is
+    /// not a registered name is a real CSS property and passes through
+    /// unchanged, with any multi-value target (`margin=0_auto`) joined into a
+    /// single declaration value.
+    ///
+    /// An arity mismatch's error span is `0..component.len()`: a `Spell` keeps
+    /// no source offsets, so the span lines up with the component in the input
+    /// only when the spell has no area, focus or effect prefix.
+    pub fn expand(&self, reg: &Registry) -> Result<Vec<Spell>, SpellError> {
+        let template = match reg.components.get(&self.component) {
+            Some(template) => template,
+            // Not a registered component: treat it as a real CSS declaration.
+            // A `Variables` target here is the baseline heuristic mistaking a
+            // multi-value property (e.g. `0_auto`) for shorthand, so join it.
+            None => {
+                let value = match &self.target {
+                    SpellTarget::CSSValue(value) => value.clone(),
+                    SpellTarget::Variables(variables) => variables.join(" "),
+                };
+                return Ok(vec![Spell {
+                    area: self.area.clone(),
+                    focus: self.focus.clone(),
+                    effect: self.effect.clone(),
+                    component: self.component.clone(),
+                    target: SpellTarget::CSSValue(value),
+                }]);
+            }
+        };
+
+        // Bind the target positionally; a lone CSS value is a single argument
+        // so that arity-1 component templates can still expand.
+        let variables = match &self.target {
+            SpellTarget::Variables(variables) => variables.clone(),
+            SpellTarget::CSSValue(value) => vec![value.clone()],
+        };
+
+        let arity = template
+            .iter()
+            .flat_map(|(_, value)| placeholders(value))
+            .max()
+            .map(|max| max + 1)
+            .unwrap_or(0);
+        if variables.len() != arity {
+            return Err(SpellError::new(
+                format!(
+                    "component '{}' expects {} argument(s) but got {}",
+                    self.component,
+                    arity,
+                    variables.len()
+                ),
+                0..self.component.len(),
+            ));
+        }
+
+        Ok(template
+            .iter()
+            .map(|(property, value)| Spell {
+                area: self.area.clone(),
+                focus: self.focus.clone(),
+                effect: self.effect.clone(),
+                component: property.clone(),
+                target: SpellTarget::CSSValue(substitute(value, &variables)),
+            })
+            .collect())
+    }
+}
+
+/// Yield every `$N` placeholder index referenced in a template value.
+fn placeholders(value: &str) -> Vec<usize> {
+    let mut indices = Vec::new();
+    let mut chars = value.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if c != '$' {
+            continue;
+        }
+        let mut digits = String::new();
+        while let Some(&(_, d)) = chars.peek() {
+            if d.is_ascii_digit() {
+                digits.push(d);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if let Ok(index) = digits.parse::<usize>() {
+            indices.push(index);
+        }
+    }
+    indices
+}
+
+/// Replace every `$N` placeholder in `template` with `variables[N]`.
+fn substitute(template: &str, variables: &[String]) -> String {
+    let mut out = String::new();
+    let mut chars = template.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        let mut digits = String::new();
+        while let Some(&(_, d)) = chars.peek() {
+            if d.is_ascii_digit() {
+                digits.push(d);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        match digits.parse::<usize>() {
+            Ok(index) => {
+                if let Some(value) = variables.get(index) {
+                    out.push_str(value);
+                }
+            }
+            Err(_) => out.push('$'),
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Spell, SpellArea, SpellBreakpoint, SpellTarget};
+    use crate::{Registry, Spell, SpellArea, SpellBreakpoint, SpellTarget};
 
     fn expect(spell_str: &str, spell: Spell) {
         assert_eq!(spell_str.parse(), Ok(spell));
@@ -247,4 +750,348 @@ mod tests {
             target: SpellTarget::CSSValue(String::from("none")),
         });
     }
+
+    fn renders(spell_str: &str, css: &str) {
+        let spell: Spell = spell_str.parse().unwrap();
+        assert_eq!(spell.to_css(), css);
+    }
+
+    #[test]
+    fn renders_simple() {
+        renders("border-radius=8px", "{ border-radius: 8px; }");
+    }
+
+    #[test]
+    fn renders_focus_and_effect() {
+        renders(
+            "{[hidden]_>_p}hover,active:color=red",
+            "[hidden] > p:hover:active { color: red; }",
+        );
+    }
+
+    #[test]
+    fn renders_breakpoint_media() {
+        renders(
+            "md__hover,active:color=red",
+            "@media (min-width: 768px) { :hover:active { color: red; } }",
+        );
+    }
+
+    #[test]
+    fn renders_custom_media_query() {
+        renders(
+            "(width>=768px)__br=0.375rem",
+            "@media (width>=768px) { { br: 0.375rem; } }",
+        );
+    }
+
+    #[test]
+    fn error_span_points_at_missing_equals() {
+        let err = "color".parse::<Spell>().unwrap_err();
+        assert_eq!(err.span, 0..5);
+        assert_eq!(err.message, "expected '=' after component but could not find one");
+    }
+
+    #[test]
+    fn error_span_offsets_into_original_input() {
+        let err = "md__bad".parse::<Spell>().unwrap_err();
+        assert_eq!(err.message, "expected '=' after component but could not find one");
+        assert_eq!(err.span, 4..7);
+    }
+
+    #[test]
+    fn error_renders_caret() {
+        let err = "color".parse::<Spell>().unwrap_err();
+        assert_eq!(
+            err.render("color"),
+            "1 | color\n    ^^^^^ expected '=' after component but could not find one\n",
+        );
+    }
+
+    fn btn_registry() -> Registry {
+        let mut reg = Registry::new();
+        reg.register(
+            "btn",
+            "border-radius: $0; background: $1; border-color: $2; color: $3;",
+        );
+        reg
+    }
+
+    #[test]
+    fn expands_component_template() {
+        let reg = btn_registry();
+        let spell: Spell = "btn=8px_lightgrey_grey_darkgrey".parse().unwrap();
+        let expanded = spell.expand(&reg).unwrap();
+        let css = super::render_all(&expanded, super::CssOptions::default());
+        assert_eq!(
+            css,
+            "{ border-radius: 8px; background: lightgrey; border-color: grey; color: darkgrey; }",
+        );
+    }
+
+    #[test]
+    fn real_property_passes_through_expand() {
+        let reg = btn_registry();
+        let spell: Spell = "border-radius=8px".parse().unwrap();
+        assert_eq!(spell.expand(&reg).unwrap(), vec![spell]);
+    }
+
+    #[test]
+    fn unregistered_multi_value_passes_through_joined() {
+        // `margin` is a real property, not a component; the baseline heuristic
+        // classifies `0_auto` as variables, but expand must still treat it as
+        // one real declaration.
+        let reg = btn_registry();
+        let spell: Spell = "margin=0_auto".parse().unwrap();
+        let expanded = spell.expand(&reg).unwrap();
+        assert_eq!(
+            super::render_all(&expanded, super::CssOptions::default()),
+            "{ margin: 0 auto; }",
+        );
+    }
+
+    #[test]
+    fn registered_component_with_lone_value_checks_arity() {
+        let reg = btn_registry();
+        // `btn=8px` parses as a single CSS value, but `btn` is registered, so
+        // it must bind positionally and fail arity rather than pass through.
+        let spell: Spell = "btn=8px".parse().unwrap();
+        let err = spell.expand(&reg).unwrap_err();
+        assert_eq!(
+            err.message,
+            "component 'btn' expects 4 argument(s) but got 1",
+        );
+    }
+
+    #[test]
+    fn arity_one_component_expands_from_lone_value() {
+        let mut reg = Registry::new();
+        reg.register("pill", "border-radius: $0;");
+        let spell: Spell = "pill=9999px".parse().unwrap();
+        let expanded = spell.expand(&reg).unwrap();
+        assert_eq!(
+            super::render_all(&expanded, super::CssOptions::default()),
+            "{ border-radius: 9999px; }",
+        );
+    }
+
+    #[test]
+    fn expand_errors_on_arity_mismatch() {
+        let reg = btn_registry();
+        let spell: Spell = "btn=8px_red".parse().unwrap();
+        let err = spell.expand(&reg).unwrap_err();
+        assert_eq!(
+            err.message,
+            "component 'btn' expects 4 argument(s) but got 2",
+        );
+    }
+
+    #[test]
+    fn minifies_rule() {
+        let spell: Spell = "md__hover,active:color=red".parse().unwrap();
+        assert_eq!(
+            spell.to_css_minified(),
+            "@media(min-width:768px){:hover:active{color:red}}",
+        );
+    }
+
+    #[test]
+    fn minify_keeps_descendant_whitespace() {
+        let spell: Spell = "{div_p}color=red".parse().unwrap();
+        assert_eq!(spell.to_css_minified(), "div p{color:red}");
+    }
+
+    #[test]
+    fn render_all_merges_shared_selector() {
+        let spells = vec![
+            "color=red".parse::<Spell>().unwrap(),
+            "background=blue".parse::<Spell>().unwrap(),
+        ];
+        assert_eq!(
+            super::render_all(&spells, super::CssOptions { minify: true }),
+            "{color:red;background:blue}",
+        );
+    }
+
+    #[test]
+    fn render_all_keeps_distinct_selectors() {
+        let spells = vec![
+            "color=red".parse::<Spell>().unwrap(),
+            "hover:color=blue".parse::<Spell>().unwrap(),
+        ];
+        assert_eq!(
+            super::render_all(&spells, super::CssOptions::default()),
+            "{ color: red; }\n:hover { color: blue; }",
+        );
+    }
+
+    #[test]
+    fn display_matches_to_css() {
+        let spell: Spell = "md__hover:display=none".parse().unwrap();
+        assert_eq!(spell.to_string(), spell.to_css());
+    }
+
+    #[test]
+    fn round_trip_invariant_is_carried_by_to_spell() {
+        let src = "md__hover:color=red";
+        let spell: Spell = src.parse().unwrap();
+        // `Display`/`to_css` render CSS and are deliberately not a spell source,
+        // so the round-trip invariant cannot go through `to_string`.
+        assert_eq!(spell.to_string(), spell.to_css());
+        assert!(spell.to_string().parse::<Spell>().is_err());
+        // `to_spell` is the canonical DSL serializer that satisfies it.
+        assert_eq!(spell.to_spell(), src);
+        assert_eq!(spell.to_spell().parse::<Spell>().unwrap(), spell);
+    }
+
+    #[test]
+    fn to_spell_round_trips_known_spells() {
+        for src in [
+            "border-radius=8px",
+            "(width>=768px)__br=0.375rem",
+            "md__{[hidden]_>_p:hover:active}color=red",
+            "md__hover,active:color=red",
+            "btn=8px_lightgrey_grey_darkgrey",
+        ] {
+            let spell: Spell = src.parse().unwrap();
+            assert_eq!(spell.to_spell(), src);
+        }
+    }
+
+    // A small deterministic PRNG so the property test needs no dev-dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() >> 33) as usize % n
+        }
+
+        fn pick<'a, T>(&mut self, choices: &'a [T]) -> &'a T {
+            &choices[self.below(choices.len())]
+        }
+    }
+
+    /// A hand-rolled `Arbitrary` strategy: produce a random `Spell` covering
+    /// every field combination (optional area as breakpoint or media query,
+    /// optional focus with combinators, multi-entry effects, CSS-value vs.
+    /// variable targets). Field values are constrained to the subset the DSL
+    /// can losslessly represent so the generator exercises the parser rather
+    /// than its input validation.
+    fn arbitrary_spell(rng: &mut Rng) -> Spell {
+        let area = match rng.below(3) {
+            0 => None,
+            1 => Some(SpellArea::Breakpoint(
+                [
+                    SpellBreakpoint::Small,
+                    SpellBreakpoint::Medium,
+                    SpellBreakpoint::Large,
+                    SpellBreakpoint::XLarge,
+                    SpellBreakpoint::XXLarge,
+                ][rng.below(5)]
+                .clone(),
+            )),
+            _ => Some(SpellArea::MediaQuery(String::from(*rng.pick(&[
+                "width>=768px",
+                "orientation:landscape",
+                "min-width:600px",
+            ])))),
+        };
+
+        let focus = if rng.below(2) == 0 {
+            None
+        } else {
+            Some(String::from(*rng.pick(&[
+                "a",
+                "div_p",
+                "_>_p",
+                "[hidden]_>_p:hover:active",
+            ])))
+        };
+
+        let effect = if rng.below(2) == 0 {
+            None
+        } else {
+            let pseudos = ["hover", "active", "focus", "visited", "first-child"];
+            let count = 1 + rng.below(3);
+            Some((0..count).map(|_| String::from(*rng.pick(&pseudos))).collect())
+        };
+
+        let component = String::from(*rng.pick(&[
+            "color",
+            "background-color",
+            "border-radius",
+            "display",
+            "width",
+        ]));
+
+        let target = if rng.below(2) == 0 {
+            SpellTarget::CSSValue(String::from(*rng.pick(&[
+                "red", "8px", "0.375rem", "none", "blue", "1px",
+            ])))
+        } else {
+            let items = ["8px", "red", "grey", "lightgrey", "darkgrey", "auto"];
+            let count = 2 + rng.below(3);
+            SpellTarget::Variables((0..count).map(|_| String::from(*rng.pick(&items))).collect())
+        };
+
+        Spell { area, focus, effect, component, target }
+    }
+
+    /// A `similar`-style line diff used to pretty-print a round-trip mismatch.
+    fn diff(left: &str, right: &str) -> String {
+        let left: Vec<&str> = left.lines().collect();
+        let right: Vec<&str> = right.lines().collect();
+        let mut out = String::new();
+        for i in 0..left.len().max(right.len()) {
+            match (left.get(i), right.get(i)) {
+                (Some(a), Some(b)) if a == b => {
+                    out.push_str("  ");
+                    out.push_str(a);
+                    out.push('\n');
+                }
+                (a, b) => {
+                    if let Some(a) = a {
+                        out.push_str("- ");
+                        out.push_str(a);
+                        out.push('\n');
+                    }
+                    if let Some(b) = b {
+                        out.push_str("+ ");
+                        out.push_str(b);
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn to_spell_parse_round_trip_invariant() {
+        let mut rng = Rng(0x5eed_c0de);
+        for _ in 0..2000 {
+            let spell = arbitrary_spell(&mut rng);
+            let serialized = spell.to_spell();
+            match serialized.parse::<Spell>() {
+                Ok(ref parsed) if *parsed == spell => {}
+                Ok(parsed) => panic!(
+                    "round-trip mismatch for {:?}\n{}",
+                    serialized,
+                    diff(&format!("{:#?}", spell), &format!("{:#?}", parsed)),
+                ),
+                Err(err) => panic!(
+                    "round-trip of {:?} failed to parse: {}",
+                    serialized, err.message
+                ),
+            }
+        }
+    }
 }